@@ -1,32 +1,31 @@
 use anyhow::{Context, Result};
 use log::{debug, error, info};
 use nix::unistd::execvpe;
-use std::ffi::CString;
+use std::collections::HashSet;
+use std::ffi::{CString, OsStr, OsString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
-pub fn run(command: &str, args: &[String], env_vars: &[String]) -> Result<()> {
-    info!("Executing: {} with args: {:?}", command, args);
+pub fn run(command: &OsStr, args: &[OsString], env_vars: &[OsString]) -> Result<()> {
+    info!("Executing: {:?} with args: {:?}", command, args);
     debug!("Environment variables: {:?}", env_vars);
 
     // Convert command and args to CString
-    let command_cstr = CString::new(command).context("Failed to convert command to CString")?;
+    let command_cstr = to_cstring(command)?;
 
     // Combine command and args for execvp
     let mut all_args = Vec::with_capacity(args.len() + 1);
     all_args.push(command_cstr.clone());
 
     for arg in args {
-        let arg_cstr =
-            CString::new(arg.as_str()).context("Failed to convert argument to CString")?;
-        all_args.push(arg_cstr);
+        all_args.push(to_cstring(arg)?);
     }
 
     // Process environment variables
-    let mut env_cstrings = Vec::new();
+    let mut env_cstrings = Vec::with_capacity(env_vars.len());
     for env_var in env_vars {
-        let env_cstr = CString::new(env_var.as_str())
-            .context("Failed to convert environment variable to CString")?;
-        env_cstrings.push(env_cstr);
+        env_cstrings.push(to_cstring(env_var)?);
     }
 
     // Execute the command, replacing the current process
@@ -40,9 +39,19 @@ pub fn run(command: &str, args: &[String], env_vars: &[String]) -> Result<()> {
     }
 }
 
+/// Convert an `OsStr` to a `CString` via its raw bytes
+///
+/// The only requirement for a `CString` is that the bytes contain no interior
+/// NUL byte, so this accepts arbitrary (including non-UTF-8) data and fails
+/// only when that invariant is violated, naming the offending value.
+fn to_cstring(value: &OsStr) -> Result<CString> {
+    CString::new(value.as_bytes())
+        .with_context(|| format!("argument {:?} contains an embedded NUL byte", value))
+}
+
 /// Get library dependencies of a binary using ldd, including necessary system paths
-pub fn get_library_dependencies(binary: &str) -> Result<Vec<String>> {
-    debug!("Detecting library dependencies for: {}", binary);
+pub fn get_library_dependencies(binary: &Path) -> Result<Vec<PathBuf>> {
+    debug!("Detecting library dependencies for: {:?}", binary);
 
     let output = Command::new("ldd")
         .arg(binary)
@@ -57,51 +66,53 @@ pub fn get_library_dependencies(binary: &str) -> Result<Vec<String>> {
         return Err(anyhow::anyhow!("ldd command failed"));
     }
 
-    let output_str = String::from_utf8(output.stdout).context("Invalid UTF-8 output from ldd")?;
+    // ldd's own output is parsed as raw bytes (not required to be valid UTF-8) so a single
+    // non-UTF-8 library path doesn't fail the whole --ldd feature.
+    let lines: Vec<&[u8]> = output.stdout.split(|&b| b == b'\n').collect();
     let mut lib_paths = Vec::new();
-    let mut parent_dirs = std::collections::HashSet::new();
+    let mut parent_dirs = HashSet::new();
 
     // First pass: Extract all library paths
-    for line in output_str.lines() {
+    for line in &lines {
         // Skip empty lines and lines without => (usually the binary name or statically linked libs)
-        if line.is_empty() || !line.contains("=>") {
+        if line.is_empty() || !bytes_contain(line, b"=>") {
             continue;
         }
 
         // Extract the library path
-        let parts: Vec<&str> = line.split_whitespace().collect();
+        let parts: Vec<&[u8]> = split_whitespace_bytes(line);
         if parts.len() >= 3 {
-            let lib_path = parts[2].trim_matches(|c| c == '(' || c == ')');
+            let lib_path = trim_parens(parts[2]);
             if !lib_path.is_empty() {
-                lib_paths.push(lib_path.to_string());
+                let lib_path = PathBuf::from(OsStr::from_bytes(lib_path));
 
                 // Add parent directory
-                if let Some(parent) = std::path::Path::new(lib_path).parent() {
-                    if let Some(parent_str) = parent.to_str() {
-                        parent_dirs.insert(parent_str.to_string());
-                    }
+                if let Some(parent) = lib_path.parent() {
+                    parent_dirs.insert(parent.to_path_buf());
                 }
+
+                lib_paths.push(lib_path);
             }
         }
     }
 
     // Second pass: Look for direct loader references (usually at the first line)
-    for line in output_str.lines() {
-        if line.contains("=>") {
+    for line in &lines {
+        if bytes_contain(line, b"=>") {
             // Skip
-        } else if line.contains("/lib64/ld-linux") || line.contains("/lib/ld-linux") {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if !parts.is_empty() {
-                let loader_path = parts[0].trim();
-                if !loader_path.is_empty() && loader_path.starts_with('/') {
-                    lib_paths.push(loader_path.to_string());
+        } else if bytes_contain(line, b"/lib64/ld-linux") || bytes_contain(line, b"/lib/ld-linux")
+        {
+            let parts: Vec<&[u8]> = split_whitespace_bytes(line);
+            if let Some(loader_path) = parts.first() {
+                if !loader_path.is_empty() && loader_path[0] == b'/' {
+                    let loader_path = PathBuf::from(OsStr::from_bytes(loader_path));
 
                     // Add parent directory
-                    if let Some(parent) = std::path::Path::new(loader_path).parent() {
-                        if let Some(parent_str) = parent.to_str() {
-                            parent_dirs.insert(parent_str.to_string());
-                        }
+                    if let Some(parent) = loader_path.parent() {
+                        parent_dirs.insert(parent.to_path_buf());
                     }
+
+                    lib_paths.push(loader_path);
                 }
             }
         }
@@ -118,8 +129,9 @@ pub fn get_library_dependencies(binary: &str) -> Result<Vec<String>> {
     ];
 
     for dir in system_dirs.iter() {
-        if std::path::Path::new(dir).exists() {
-            parent_dirs.insert(dir.to_string());
+        let dir = Path::new(dir);
+        if dir.exists() {
+            parent_dirs.insert(dir.to_path_buf());
         }
     }
 
@@ -130,4 +142,79 @@ pub fn get_library_dependencies(binary: &str) -> Result<Vec<String>> {
 
     debug!("Detected library paths: {:?}", lib_paths);
     Ok(lib_paths)
-}
\ No newline at end of file
+}
+
+fn bytes_contain(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+fn split_whitespace_bytes(line: &[u8]) -> Vec<&[u8]> {
+    line.split(|&b| b == b' ' || b == b'\t')
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn trim_parens(mut bytes: &[u8]) -> &[u8] {
+    while let [b'(' | b')', rest @ ..] = bytes {
+        bytes = rest;
+    }
+    while let [rest @ .., b'(' | b')'] = bytes {
+        bytes = rest;
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStringExt;
+
+    #[test]
+    fn test_to_cstring_accepts_non_utf8_bytes() {
+        // 0xff is not valid UTF-8 on its own, so this OsString can't be built from a &str.
+        let value = OsString::from_vec(vec![b'a', 0xff, b'b']);
+        let cstr = to_cstring(&value).unwrap();
+
+        assert_eq!(cstr.as_bytes(), &[b'a', 0xff, b'b']);
+    }
+
+    #[test]
+    fn test_to_cstring_rejects_embedded_nul() {
+        let value = OsString::from_vec(vec![b'a', 0, b'b']);
+        let err = to_cstring(&value).unwrap_err();
+
+        assert!(err.to_string().contains("embedded NUL byte"));
+    }
+
+    #[test]
+    fn test_trim_parens() {
+        assert_eq!(trim_parens(b"(0x00007f)"), b"0x00007f");
+        assert_eq!(trim_parens(b"no-parens"), b"no-parens");
+    }
+
+    #[test]
+    fn test_split_whitespace_bytes() {
+        assert_eq!(
+            split_whitespace_bytes(b"libc.so.6 => /lib/libc.so.6 (0x1)"),
+            vec![
+                &b"libc.so.6"[..],
+                &b"=>"[..],
+                &b"/lib/libc.so.6"[..],
+                &b"(0x1)"[..],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_library_path_parsing_tolerates_non_utf8_bytes() {
+        // A non-UTF-8 byte in a library path must not make parsing fail: the path is only
+        // ever handled as OsStr/PathBuf bytes, never decoded as a `str`.
+        let line = b"\tlibfoo.so => /opt/\xffoo/libfoo.so (0x1)";
+
+        let parts = split_whitespace_bytes(line);
+        assert_eq!(parts.len(), 4);
+
+        let lib_path = PathBuf::from(OsStr::from_bytes(trim_parens(parts[2])));
+        assert_eq!(lib_path, PathBuf::from(OsStr::from_bytes(b"/opt/\xffoo/libfoo.so")));
+    }
+}
@@ -5,9 +5,11 @@ mod utils;
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
 use log::{debug, error, info};
+use std::env;
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::process;
-use which::which;
+use which::which_in;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -47,9 +49,19 @@ struct Cli {
     #[arg(long)]
     best_effort: bool,
 
-    /// Environment variables to pass to the sandboxed command (KEY=VALUE or just KEY to pass current value)
+    /// Environment variables to pass to the sandboxed command (KEY=VALUE, just KEY to pass
+    /// the current value, or a glob pattern like 'LC_*' to pass all matching current variables)
     #[arg(long = "env", value_name = "VAR")]
-    env_vars: Vec<String>,
+    env_vars: Vec<OsString>,
+
+    /// Seed the sandboxed command's environment from the current process environment
+    /// before applying --env-file and --env overrides (default is a clean-slate environment)
+    #[arg(long)]
+    inherit_env: bool,
+
+    /// Load KEY=VALUE environment variables from a file (blank lines and '#' comments ignored)
+    #[arg(long = "env-file", value_name = "PATH")]
+    env_file: Option<PathBuf>,
 
     /// Allow unrestricted filesystem access
     #[arg(long)]
@@ -67,9 +79,23 @@ struct Cli {
     #[arg(long)]
     ldd: bool,
 
+    /// Override the PATH used to resolve the command and for --rox-path (DIR:DIR:..., using
+    /// the platform path separator, same format as $PATH)
+    #[arg(long = "path", value_name = "DIR:DIR:...")]
+    path: Option<OsString>,
+
+    /// Automatically add the resolved binary's directory to --rox
+    #[arg(long = "rox-path")]
+    rox_path: bool,
+
+    /// Automatically add every existing directory on the (possibly --path-overridden) PATH to
+    /// --rox, instead of just the resolved binary's directory
+    #[arg(long = "rox-path-all")]
+    rox_path_all: bool,
+
     /// Command to run and its arguments
     #[arg(trailing_var_arg = true, required = true)]
-    command: Vec<String>,
+    command: Vec<OsString>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Debug, ValueEnum)]
@@ -119,13 +145,18 @@ fn main() -> Result<()> {
     } else {
         Vec::new()
     };
-    
-    info!("Command: {}, args: {:?}", command, args);
-    
-    // Find the full path to the binary
-    let binary_path = which(&command).with_context(|| format!("Failed to find binary: {}", command))?;
-    let binary_path_str = binary_path.to_string_lossy().to_string();
-    
+
+    info!("Command: {:?}, args: {:?}", command, args);
+
+    // Find the full path to the binary, honoring --path if given, falling back to $PATH
+    let search_path = cli.path.clone().or_else(|| env::var_os("PATH"));
+    let binary_path = which_in(
+        &command,
+        search_path,
+        env::current_dir().context("Failed to get current directory")?,
+    )
+    .with_context(|| format!("Failed to find binary: {:?}", command))?;
+
     // Initialize sandbox configuration
     let mut sandbox_config = sandbox::Config::new();
     
@@ -142,17 +173,17 @@ fn main() -> Result<()> {
     
     // Add executable to read-only executable paths if requested
     if cli.add_exec {
-        debug!("Adding executable path: {}", binary_path_str);
+        debug!("Adding executable path: {:?}", binary_path);
         sandbox_config.read_only_executable_paths.push(binary_path.clone());
     }
-    
+
     // Add library dependencies if requested
     if cli.ldd {
-        match exec::get_library_dependencies(&binary_path_str) {
+        match exec::get_library_dependencies(&binary_path) {
             Ok(lib_paths) => {
                 for lib_path in lib_paths {
-                    debug!("Adding library path: {}", lib_path);
-                    sandbox_config.read_only_executable_paths.push(PathBuf::from(lib_path));
+                    debug!("Adding library path: {:?}", lib_path);
+                    sandbox_config.read_only_executable_paths.push(lib_path);
                 }
             },
             Err(err) => {
@@ -162,8 +193,28 @@ fn main() -> Result<()> {
         }
     }
     
+    // Grant execute access to the resolved binary's directory and/or PATH search dirs
+    if cli.rox_path || cli.rox_path_all {
+        if let Some(parent) = binary_path.parent() {
+            debug!("Adding resolved binary's directory: {:?}", parent);
+            sandbox_config
+                .read_only_executable_paths
+                .push(parent.to_path_buf());
+        }
+    }
+
+    if cli.rox_path_all {
+        for dir in utils::path_search_dirs(cli.path.as_deref()) {
+            if dir.exists() {
+                debug!("Adding PATH directory: {:?}", dir);
+                sandbox_config.read_only_executable_paths.push(dir);
+            }
+        }
+    }
+
     // Process environment variables
-    let env_vars = utils::process_environment_vars(&cli.env_vars);
+    let env_vars = utils::build_env(cli.inherit_env, cli.env_file.as_deref(), &cli.env_vars)
+        .context("Failed to resolve environment variables")?;
     
     // Apply sandbox configuration
     if let Err(err) = sandbox::apply(&sandbox_config) {
@@ -172,11 +223,39 @@ fn main() -> Result<()> {
     }
     
     // Execute the command (this should replace the current process)
-    if let Err(err) = exec::run(&binary_path_str, &args, &env_vars) {
+    if let Err(err) = exec::run(binary_path.as_os_str(), &args, &env_vars) {
         error!("Failed to execute command: {}", err);
         process::exit(1);
     }
     
     // We should never reach this point unless exec::run fails
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rox_path_does_not_consume_the_command() {
+        let cli = Cli::try_parse_from(["rstrict", "--rox-path", "ls", "-la"]).unwrap();
+
+        assert!(cli.rox_path);
+        assert!(!cli.rox_path_all);
+        assert_eq!(
+            cli.command,
+            vec![OsString::from("ls"), OsString::from("-la")]
+        );
+    }
+
+    #[test]
+    fn test_rox_path_all_does_not_consume_the_command() {
+        let cli = Cli::try_parse_from(["rstrict", "--rox-path-all", "ls", "-la"]).unwrap();
+
+        assert!(cli.rox_path_all);
+        assert_eq!(
+            cli.command,
+            vec![OsString::from("ls"), OsString::from("-la")]
+        );
+    }
 }
\ No newline at end of file
@@ -1,84 +1,267 @@
+use std::collections::HashMap;
 use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
 
-/// Process environment variables from CLI flags
-///
-/// This function processes strings in either of these formats:
-/// - KEY=VALUE: Uses the provided value
-/// - KEY: Takes the value from the current environment
+use anyhow::{Context, Result};
+use glob::Pattern;
+
+/// Directories to search for an executable: either `path_override` (in the same
+/// colon-separated format as `$PATH`) or the current process's `PATH` environment variable.
+pub fn path_search_dirs(path_override: Option<&OsStr>) -> Vec<PathBuf> {
+    match path_override {
+        Some(path) => env::split_paths(path).collect(),
+        None => env::var_os("PATH")
+            .map(|path| env::split_paths(&path).collect())
+            .unwrap_or_default(),
+    }
+}
+
+/// Resolve the final set of environment variables to hand to the sandboxed
+/// command.
 ///
-/// Returns a vector of environment variables in the format KEY=VALUE
-pub fn process_environment_vars(env_flags: &[String]) -> Vec<String> {
-    let mut result = Vec::new();
+/// Resolution order, with later entries overriding earlier ones on key
+/// collisions:
+/// 1. The current process environment, if `inherit_env` is set
+/// 2. `KEY=VALUE` lines loaded from `env_file`
+/// 3. Explicit `--env` entries, each of which is one of:
+///    - `KEY=VALUE`: uses the provided value
+///    - `KEY`: takes the value from the current environment
+///    - a shell-style glob pattern (e.g. `LC_*`): expands against the names
+///      of all variables in the current environment
+pub fn build_env(
+    inherit_env: bool,
+    env_file: Option<&Path>,
+    env_flags: &[OsString],
+) -> Result<Vec<OsString>> {
+    let mut vars: HashMap<OsString, OsString> = HashMap::new();
+
+    if inherit_env {
+        vars.extend(env::vars_os());
+    }
+
+    if let Some(path) = env_file {
+        for (key, value) in parse_env_file(path)? {
+            vars.insert(key, value);
+        }
+    }
 
     for env_flag in env_flags {
-        // If the flag is just a key (no = sign), get the value from the current environment
-        if !env_flag.contains('=') {
-            if let Ok(val) = env::var(env_flag) {
-                result.push(format!("{}={}", env_flag, val));
+        apply_env_flag(env_flag, &mut vars);
+    }
+
+    Ok(vars
+        .into_iter()
+        .map(|(key, value)| join_key_value(&key, &value))
+        .collect())
+}
+
+/// Apply a single `--env` entry to `vars` (see [`build_env`] for the
+/// supported forms).
+fn apply_env_flag(env_flag: &OsStr, vars: &mut HashMap<OsString, OsString>) {
+    let bytes = env_flag.as_bytes();
+
+    if let Some(eq_pos) = bytes.iter().position(|&b| b == b'=') {
+        let key = OsStr::from_bytes(&bytes[..eq_pos]).to_os_string();
+        let value = OsStr::from_bytes(&bytes[eq_pos + 1..]).to_os_string();
+        vars.insert(key, value);
+        return;
+    }
+
+    if let Some(key) = env_flag.to_str()
+        && is_glob_pattern(key)
+    {
+        if let Ok(pattern) = Pattern::new(key) {
+            for (name, value) in env::vars_os() {
+                if name.to_str().is_some_and(|name| pattern.matches(name)) {
+                    vars.insert(name, value);
+                }
             }
-        } else {
-            // Flag already contains the value (KEY=VALUE format)
-            result.push(env_flag.clone());
         }
+        return;
     }
 
-    result
+    if let Some(val) = env::var_os(env_flag) {
+        vars.insert(env_flag.to_os_string(), val);
+    }
+}
+
+fn is_glob_pattern(key: &str) -> bool {
+    key.contains(['*', '?', '['])
+}
+
+/// Parse a `--env-file`: `KEY=VALUE` lines, ignoring blank lines and `#`
+/// comments.
+fn parse_env_file(path: &Path) -> Result<Vec<(OsString, OsString)>> {
+    let contents =
+        fs::read(path).with_context(|| format!("Failed to read env file: {:?}", path))?;
+    let mut result = Vec::new();
+
+    for line in contents.split(|&b| b == b'\n') {
+        let line = trim_bytes(line);
+        if line.is_empty() || line[0] == b'#' {
+            continue;
+        }
+
+        if let Some(eq_pos) = line.iter().position(|&b| b == b'=') {
+            let key = OsStr::from_bytes(&line[..eq_pos]).to_os_string();
+            let value = OsStr::from_bytes(&line[eq_pos + 1..]).to_os_string();
+            result.push((key, value));
+        }
+    }
+
+    Ok(result)
+}
+
+fn trim_bytes(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|b| !b.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    &bytes[start..end]
+}
+
+/// Join a key and value into a single `KEY=VALUE` `OsString`
+fn join_key_value(key: &OsStr, value: &OsStr) -> OsString {
+    let mut bytes = Vec::with_capacity(key.as_bytes().len() + value.as_bytes().len() + 1);
+    bytes.extend_from_slice(key.as_bytes());
+    bytes.push(b'=');
+    bytes.extend_from_slice(value.as_bytes());
+    OsString::from_vec(bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
+    use std::io::Write;
+
+    fn sorted(mut vars: Vec<OsString>) -> Vec<OsString> {
+        vars.sort();
+        vars
+    }
 
     #[test]
-    fn test_process_environment_vars_key_value() {
-        let vars = vec![String::from("KEY1=value1"), String::from("KEY2=value2")];
-        let result = process_environment_vars(&vars);
-        
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0], "KEY1=value1");
-        assert_eq!(result[1], "KEY2=value2");
+    fn test_path_search_dirs_uses_override() {
+        let override_path = env::join_paths([PathBuf::from("/override/one"), PathBuf::from("/override/two")]).unwrap();
+        let result = path_search_dirs(Some(override_path.as_os_str()));
+
+        assert_eq!(
+            result,
+            vec![PathBuf::from("/override/one"), PathBuf::from("/override/two")]
+        );
+    }
+
+    #[test]
+    fn test_path_search_dirs_falls_back_to_path_env() {
+        let original = env::var_os("PATH");
+        env::set_var("PATH", "/fallback/one:/fallback/two");
+
+        let result = path_search_dirs(None);
+
+        match original {
+            Some(path) => env::set_var("PATH", path),
+            None => env::remove_var("PATH"),
+        }
+
+        assert_eq!(
+            result,
+            vec![PathBuf::from("/fallback/one"), PathBuf::from("/fallback/two")]
+        );
+    }
+
+    #[test]
+    fn test_build_env_key_value() {
+        let vars = vec![OsString::from("KEY1=value1"), OsString::from("KEY2=value2")];
+        let result = sorted(build_env(false, None, &vars).unwrap());
+
+        assert_eq!(
+            result,
+            vec![OsString::from("KEY1=value1"), OsString::from("KEY2=value2")]
+        );
     }
 
     #[test]
-    fn test_process_environment_vars_existing_key() {
+    fn test_build_env_existing_key() {
         env::set_var("TEST_ENV_VAR", "test_value");
-        
-        let vars = vec![String::from("TEST_ENV_VAR")];
-        let result = process_environment_vars(&vars);
-        
-        assert_eq!(result.len(), 1);
-        assert_eq!(result[0], "TEST_ENV_VAR=test_value");
-        
+
+        let vars = vec![OsString::from("TEST_ENV_VAR")];
+        let result = build_env(false, None, &vars).unwrap();
+
+        assert_eq!(result, vec![OsString::from("TEST_ENV_VAR=test_value")]);
+
         env::remove_var("TEST_ENV_VAR");
     }
 
     #[test]
-    fn test_process_environment_vars_nonexistent_key() {
+    fn test_build_env_nonexistent_key() {
         env::remove_var("NONEXISTENT_TEST_VAR");
-        
-        let vars = vec![String::from("NONEXISTENT_TEST_VAR")];
-        let result = process_environment_vars(&vars);
-        
+
+        let vars = vec![OsString::from("NONEXISTENT_TEST_VAR")];
+        let result = build_env(false, None, &vars).unwrap();
+
         assert_eq!(result.len(), 0);
     }
 
     #[test]
-    fn test_process_environment_vars_mixed() {
-        env::set_var("TEST_ENV_VAR", "test_value");
-        
-        let vars = vec![
-            String::from("KEY1=value1"),
-            String::from("TEST_ENV_VAR"),
-            String::from("NONEXISTENT_TEST_VAR")
-        ];
-        
-        let result = process_environment_vars(&vars);
-        
-        assert_eq!(result.len(), 2);
-        assert_eq!(result[0], "KEY1=value1");
-        assert_eq!(result[1], "TEST_ENV_VAR=test_value");
-        
-        env::remove_var("TEST_ENV_VAR");
+    fn test_build_env_explicit_overrides_inherited() {
+        env::set_var("TEST_ENV_OVERRIDE", "from_process");
+
+        let vars = vec![OsString::from("TEST_ENV_OVERRIDE=from_flag")];
+        let result = build_env(true, None, &vars).unwrap();
+
+        assert!(result.contains(&OsString::from("TEST_ENV_OVERRIDE=from_flag")));
+
+        env::remove_var("TEST_ENV_OVERRIDE");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_build_env_file_overridden_by_explicit() {
+        let path = env::temp_dir().join(format!("rstrict-test-env-{}", std::process::id()));
+        {
+            let mut file = std::fs::File::create(&path).unwrap();
+            writeln!(file, "# a comment").unwrap();
+            writeln!(file).unwrap();
+            writeln!(file, "FROM_FILE=file_value").unwrap();
+            writeln!(file, "SHARED=file_value").unwrap();
+        }
+
+        let vars = vec![OsString::from("SHARED=flag_value")];
+        let result = sorted(build_env(false, Some(&path), &vars).unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                OsString::from("FROM_FILE=file_value"),
+                OsString::from("SHARED=flag_value"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_env_glob_pattern() {
+        env::set_var("TEST_GLOB_ONE", "one");
+        env::set_var("TEST_GLOB_TWO", "two");
+
+        let vars = vec![OsString::from("TEST_GLOB_*")];
+        let result = sorted(build_env(false, None, &vars).unwrap());
+
+        assert_eq!(
+            result,
+            vec![
+                OsString::from("TEST_GLOB_ONE=one"),
+                OsString::from("TEST_GLOB_TWO=two"),
+            ]
+        );
+
+        env::remove_var("TEST_GLOB_ONE");
+        env::remove_var("TEST_GLOB_TWO");
+    }
+}